@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::{
+    error,
+    parser::{Node, Type},
+    scanner::{Position, Token},
+};
+
+// Walks the AST after parsing and infers/validates the `Type` of every
+// expression, modeled on Dust's `WrongTypeCombination` check. Each
+// diagnostic is reported at the span of the offending subexpression,
+// using the `Position` carried on every `Node`.
+pub struct TypeChecker {
+    source: String,
+    // One scope per nesting level (only the implicit top-level scope and,
+    // while checking a function body, that function's own scope). Pushed
+    // on entering a function body and popped on leaving it, so a
+    // parameter name can't leak into or clobber a sibling function's
+    // scope. Lookups walk outward so a function body can still see
+    // top-level bindings.
+    scope: Vec<HashMap<String, Type>>,
+    pub errors: usize,
+}
+
+impl TypeChecker {
+    pub fn new(source: String) -> TypeChecker {
+        TypeChecker {
+            source,
+            scope: vec![HashMap::new()],
+            errors: 0,
+        }
+    }
+
+    pub fn check(&mut self, ast: &[Node]) {
+        for node in ast {
+            self.check_node(node);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Type> {
+        self.scope.iter().rev().find_map(|scope| scope.get(name).cloned())
+    }
+
+    fn bind(&mut self, name: String, ty: Type) {
+        self.scope.last_mut().unwrap().insert(name, ty);
+    }
+
+    fn error(&mut self, message: &str, position: Position) {
+        error::print(
+            message,
+            &self.source.split('\n').collect::<Vec<&str>>(),
+            position.line.saturating_sub(1),
+            position.col.saturating_sub(1),
+            error::ErrorType::Fatal,
+        );
+        println!();
+
+        self.errors += 1;
+    }
+
+    fn check_node(&mut self, node: &Node) -> Option<Type> {
+        match node {
+            Node::String(..) => Some(Type::String),
+            Node::Number(..) => Some(Type::Number),
+            Node::Float(..) => Some(Type::Float),
+            Node::Boolean(..) => Some(Type::Boolean),
+            // An identifier the checker hasn't seen a binding for yet isn't
+            // this pass's problem to report; just treat it as untyped.
+            Node::Identifier(name, _) => self.lookup(name),
+
+            // A `let` doesn't produce a value itself, so it can't satisfy or
+            // violate a declared return type — only a bare trailing
+            // expression can. Bind the name for later lookups and leave the
+            // statement's own type unknown, same as a destructure.
+            Node::VariableAssignment { name, value, .. } => {
+                let value_type = self.check_node(value);
+
+                if let Some(value_type) = value_type {
+                    self.bind(name.clone(), value_type);
+                }
+
+                None
+            }
+            Node::VariableDestructureAssignment { properties, .. } => {
+                for (name, _) in properties {
+                    self.bind(name.clone(), Type::Custom("unknown".to_string()));
+                }
+
+                None
+            }
+
+            Node::Unary {
+                left,
+                operation,
+                position,
+            } => {
+                let left_type = self.check_node(left);
+                self.check_unary(*operation, left_type, *position)
+            }
+            Node::Binary {
+                left,
+                right,
+                operation,
+                position,
+            } => {
+                let left_type = self.check_node(left);
+                let right_type = self.check_node(right);
+
+                self.check_binary(operation, left_type, right_type, *position)
+            }
+            Node::Logical {
+                left,
+                right,
+                position,
+                ..
+            } => {
+                let left_type = self.check_node(left);
+                let right_type = self.check_node(right);
+
+                for operand in [left_type, right_type].into_iter().flatten() {
+                    if operand != Type::Boolean {
+                        self.error(
+                            &format!("TypeError: expected Boolean, found {:?}", operand),
+                            *position,
+                        );
+                    }
+                }
+
+                Some(Type::Boolean)
+            }
+
+            // No call-site argument checking yet: declarations aren't
+            // tracked by name, so there's nothing to check a call against.
+            Node::FunctionCall { arguments, .. } => {
+                for argument in arguments {
+                    self.check_node(argument);
+                }
+
+                None
+            }
+            Node::FunctionDeclaration {
+                params,
+                body,
+                return_type,
+                position,
+                ..
+            } => {
+                self.scope.push(HashMap::new());
+
+                for (param_name, param_type) in params {
+                    self.bind(param_name.clone(), param_type.clone());
+                }
+
+                let mut last = None;
+                for statement in body {
+                    last = self.check_node(statement);
+                }
+
+                self.scope.pop();
+
+                if let (Some(declared), Some(actual)) = (return_type, &last) {
+                    if declared != actual {
+                        let error_position =
+                            body.last().map(|node| node.position()).unwrap_or(*position);
+
+                        self.error(
+                            &format!("TypeError: expected {:?}, found {:?}", declared, actual),
+                            error_position,
+                        );
+                    }
+                }
+
+                None
+            }
+        }
+    }
+
+    fn check_unary(&mut self, operation: char, operand: Option<Type>, position: Position) -> Option<Type> {
+        let operand = operand?;
+
+        match operation {
+            '-' => match operand {
+                Type::Number | Type::Float => Some(operand),
+                other => {
+                    self.error(
+                        &format!("TypeError: expected Number, found {:?}", other),
+                        position,
+                    );
+                    None
+                }
+            },
+            '!' => match operand {
+                Type::Boolean => Some(operand),
+                other => {
+                    self.error(
+                        &format!("TypeError: expected Boolean, found {:?}", other),
+                        position,
+                    );
+                    None
+                }
+            },
+            _ => None,
+        }
+    }
+
+    // Illegal operand combinations are reported and the expression's type
+    // is left unknown, same as an unresolved identifier, so one bad
+    // subexpression doesn't cascade into unrelated errors above it.
+    fn check_binary(
+        &mut self,
+        operation: &Token,
+        left: Option<Type>,
+        right: Option<Type>,
+        position: Position,
+    ) -> Option<Type> {
+        let (left, right) = (left?, right?);
+
+        match operation {
+            Token::OpAdd => match (&left, &right) {
+                (Type::Number, Type::Number) => Some(Type::Number),
+                (Type::Float, Type::Float) => Some(Type::Float),
+                (Type::String, Type::String) => Some(Type::String),
+                _ => self.mismatch_error(left, right, position),
+            },
+            Token::OpSub | Token::OpMul | Token::OpDiv | Token::OpMod => match (&left, &right) {
+                (Type::Number, Type::Number) => Some(Type::Number),
+                (Type::Float, Type::Float) => Some(Type::Float),
+                _ => self.mismatch_error(left, right, position),
+            },
+            Token::OpEq
+            | Token::OpUneq
+            | Token::OpLess
+            | Token::OpMore
+            | Token::OpLessEq
+            | Token::OpMoreEq => {
+                if left != right {
+                    self.mismatch_error(left, right, position)
+                } else {
+                    Some(Type::Boolean)
+                }
+            }
+            Token::BitwiseAnd | Token::BitwiseOr | Token::BitwiseXor => match (&left, &right) {
+                (Type::Number, Type::Number) => Some(Type::Number),
+                _ => self.mismatch_error(left, right, position),
+            },
+            _ => None,
+        }
+    }
+
+    fn mismatch_error(&mut self, left: Type, right: Type, position: Position) -> Option<Type> {
+        self.error(
+            &format!("TypeError: expected {:?}, found {:?}", left, right),
+            position,
+        );
+        None
+    }
+}
@@ -1,26 +1,61 @@
 use colored::Colorize;
-use std::{fs::read_to_string, time::Instant};
+use std::{env, fs::read_to_string, process::exit, time::Instant};
 
+mod checker;
 mod error;
 mod parser;
 mod scanner;
 
+use checker::TypeChecker;
 use parser::Parser;
 use scanner::Scanner;
 
 fn main() {
     let start = Instant::now();
 
-    let contents: String = read_to_string("main.lc").unwrap();
+    let mut path: Option<String> = None;
+    let mut dump_tokens = false;
+    let mut dump_ast = false;
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--tokens" => dump_tokens = true,
+            "--ast" => dump_ast = true,
+            _ => path = Some(arg),
+        }
+    }
+
+    let path = path.unwrap_or_else(|| {
+        eprintln!("{}", "Usage: lace <file> [--tokens] [--ast]".red());
+        exit(1);
+    });
+
+    let contents: String = read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("{}", format!("Could not read '{}': {}", path, err).red());
+        exit(1);
+    });
     let mut scanner = Scanner::new(contents.clone(), 0);
     scanner.scan();
 
-    let mut parser: Parser = Parser::new(scanner.tokens, contents);
+    if dump_tokens {
+        for (token, position) in scanner.tokens.iter().zip(scanner.positions.iter()) {
+            println!("{}:{} {:?}", position.line, position.col, token);
+        }
+        return;
+    }
+
+    let mut parser: Parser = Parser::new(scanner.tokens, scanner.positions, contents.clone());
     parser.parse();
 
-    println!("{:#?}", parser.ast);
+    if dump_ast {
+        println!("{:#?}", parser.ast);
+    }
+
+    let mut checker = TypeChecker::new(contents);
+    checker.check(&parser.ast);
+
     println!(
-        "{} in {:?} with {}",
+        "{} in {:?} with {} and {}",
         "Compiled".green().bold(),
         start.elapsed(),
         format!(
@@ -32,12 +67,16 @@ fn main() {
                 "warnings"
             }
         )
+        .yellow(),
+        format!(
+            "{} type {}",
+            checker.errors,
+            if checker.errors == 1 {
+                "error"
+            } else {
+                "errors"
+            }
+        )
         .yellow()
     )
-
-    // error::print(
-    //    "Invalid return type. Expected string.",
-    //    &vec!["pub fn foo() -> string {", "    return 89;", "}"],
-    //    1, 11
-    // );
 }
@@ -1,8 +1,12 @@
 use colored::Colorize;
 use std::process::exit;
 
-use crate::{error, scanner::Token};
+use crate::{
+    error,
+    scanner::{Position, Token},
+};
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     String,
     Number,
@@ -11,68 +15,120 @@ pub enum Type {
     Custom(String),
 }
 
+impl Type {
+    fn from_name(name: &str) -> Type {
+        match name {
+            "string" => Type::String,
+            "number" => Type::Number,
+            "float" => Type::Float,
+            "boolean" => Type::Boolean,
+            other => Type::Custom(other.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Node {
-    String(String),
-    Number(i64),
-    Float(f64),
-    Boolean(bool),
-    Identifier(String),
+    String(String, Position),
+    Number(i64, Position),
+    Float(f64, Position),
+    Boolean(bool, Position),
+    Identifier(String, Position),
 
     VariableAssignment {
         name: String,
         value: Box<Node>,
         mutable: bool,
+        position: Position,
     },
     VariableDestructureAssignment {
         properties: Vec<(String, bool /* mutable */)>,
         value: Box<Node>, // can only be an identifier
         mutable: bool,
+        position: Position,
     },
     Binary {
         left: Box<Node>,
         right: Box<Node>,
         operation: Token,
+        position: Position,
+    },
+    // Kept separate from `Binary` so `and`/`or` can short-circuit instead of
+    // always evaluating both sides.
+    Logical {
+        left: Box<Node>,
+        right: Box<Node>,
+        operation: Token,
+        position: Position,
     },
     Unary {
         left: Box<Node>,
         operation: char,
+        position: Position,
     },
     FunctionCall {
         name: String,
         arguments: Vec<Node>,
+        position: Position,
+    },
+    FunctionDeclaration {
+        name: String,
+        params: Vec<(String, Type)>,
+        return_type: Option<Type>,
+        body: Vec<Node>,
+        public: bool,
+        position: Position,
+    },
+}
+
+impl Node {
+    // The span a diagnostic about this node (or a value derived from it,
+    // like a function's inferred return type) should point at.
+    pub fn position(&self) -> Position {
+        match self {
+            Node::String(_, position)
+            | Node::Number(_, position)
+            | Node::Float(_, position)
+            | Node::Boolean(_, position)
+            | Node::Identifier(_, position) => *position,
+            Node::VariableAssignment { position, .. }
+            | Node::VariableDestructureAssignment { position, .. }
+            | Node::Binary { position, .. }
+            | Node::Logical { position, .. }
+            | Node::Unary { position, .. }
+            | Node::FunctionCall { position, .. }
+            | Node::FunctionDeclaration { position, .. } => *position,
+        }
     }
 }
 
 pub struct Parser {
     tokens: Vec<Token>,
+    positions: Vec<Position>,
     current: usize, // current token
     source: String,
-    current_line: usize,
 
     pub ast: Vec<Node>,
     pub warnings: usize,
+    pub errors: Vec<(String, Position)>,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>, source: String) -> Parser {
+    pub fn new(tokens: Vec<Token>, positions: Vec<Position>, source: String) -> Parser {
         Parser {
             tokens,
+            positions,
             current: 0,
             source,
             ast: vec![],
-            current_line: 0,
             warnings: 0,
+            errors: vec![],
         }
     }
 
     fn advance(&mut self) -> Token {
         self.current += 1;
 
-        if self.get_current() == Token::OpNewline {
-            self.current_line += 1;
-        }
-
         if self.current < self.tokens.len() {
             return self.get_current();
         } else {
@@ -80,31 +136,58 @@ impl Parser {
         }
     }
 
-    fn expect(&mut self, token: Token, error: &str) {
+    fn expect(&mut self, token: Token, error: &str) -> Result<(), ()> {
         if self.advance() != token {
-            self.error(error);
+            return self.error(error);
         }
+
+        Ok(())
     }
 
-    fn error(&mut self, error: &str) -> ! {
-        error::print(
-            error,
-            &self.source.split('\n').collect::<Vec<&str>>(),
-            self.current_line,
-            0,
-            error::ErrorType::Fatal,
-        );
+    // Records the diagnostic and synchronizes to the next statement
+    // boundary instead of exiting immediately, so a single bad statement
+    // doesn't stop the rest of the file from being checked. Generic over
+    // the success type so it can stand in for any parse function's `Err`
+    // arm, same as `!` did before.
+    fn error<T>(&mut self, error: &str) -> Result<T, ()> {
+        let position = self.get_current_position();
 
-        println!("{}", "Could not compile due to error above.".red());
-        exit(0);
+        self.errors.push((error.to_string(), position));
+        self.synchronize();
+
+        Err(())
+    }
+
+    // Advances past the rest of the broken statement so the next call to
+    // `statement()` starts on a clean boundary instead of cascading into
+    // more spurious errors.
+    fn synchronize(&mut self) {
+        while !self.is_at_end() {
+            match self.get_current() {
+                Token::OpNewline | Token::OpSemicolon => {
+                    self.advance();
+                    return;
+                }
+                Token::Keyword(ref kw)
+                    if matches!(kw.as_str(), "let" | "fn" | "pub" | "struct" | "enum") =>
+                {
+                    return;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
     }
 
     fn warn(&mut self, warning: &str) {
+        let position = self.get_current_position();
+
         error::print(
             warning,
             &self.source.split('\n').collect::<Vec<&str>>(),
-            self.current_line,
-            0,
+            position.line.saturating_sub(1),
+            position.col.saturating_sub(1),
             error::ErrorType::Warning,
         );
         println!("");
@@ -126,17 +209,34 @@ impl Parser {
         }
     }
 
-    fn value(&mut self) -> Node {
+    // Position of the current token, used to point diagnostics at the
+    // right line/col. Clamps to the last known position past the end of
+    // the token stream, same as `get_current` clamps to `Token::End`.
+    #[inline(always)]
+    fn get_current_position(&self) -> Position {
+        if self.current < self.positions.len() {
+            self.positions[self.current]
+        } else {
+            self.positions
+                .last()
+                .copied()
+                .unwrap_or(Position { line: 1, col: 1 })
+        }
+    }
+
+    fn value(&mut self) -> Result<Node, ()> {
+        let start = self.get_current_position();
         let current = self.get_current();
         self.advance();
 
         match current {
-            Token::BooleanLiteral(boolean) => Node::Boolean(boolean),
-            Token::NumberLiteral(number) => Node::Number(number),
-            Token::StringLiteral(ref string) => Node::String(string.to_string()),
+            Token::BooleanLiteral(boolean) => Ok(Node::Boolean(boolean, start)),
+            Token::NumberLiteral(number) => Ok(Node::Number(number, start)),
+            Token::FloatLiteral(float) => Ok(Node::Float(float, start)),
+            Token::StringLiteral(ref string) => Ok(Node::String(string.to_string(), start)),
             Token::Identifier(ref string) => {
                 let identifier = string.to_string();
-                
+
                 if self.get_current() == Token::ParenLeft {
                     let mut arguments: Vec<Node> = vec![];
                     self.advance();
@@ -148,31 +248,31 @@ impl Parser {
                         } else if self.get_current() == Token::OpComma {
                             self.advance();
                         } else {
-                            arguments.push(self.expression());
-                            
-                            // println!("{:?}", self.get_current());
-                            // self.error("SyntaxError: Expected `)` or `,`.")
+                            arguments.push(self.expression()?);
                         }
                     }
 
-                    Node::FunctionCall {
+                    Ok(Node::FunctionCall {
                         name: identifier,
-                        arguments
-                    }
+                        arguments,
+                        position: start,
+                    })
                 } else {
-                    Node::Identifier(identifier)
+                    Ok(Node::Identifier(identifier, start))
                 }
-            },
+            }
             Token::OpNot => match self.get_current() {
                 Token::BooleanLiteral(_)
                 | Token::StringLiteral(_)
                 | Token::NumberLiteral(_)
+                | Token::FloatLiteral(_)
                 | Token::Identifier(_)
                 | Token::OpNot
-                | Token::OpSub => Node::Unary {
-                    left: Box::new(self.value()),
+                | Token::OpSub => Ok(Node::Unary {
+                    left: Box::new(self.value()?),
                     operation: '!',
-                },
+                    position: start,
+                }),
                 a => self.error(&format!(
                     "SyntaxError: Unexpected token [2] `{:?}`. Expected value",
                     a
@@ -182,26 +282,28 @@ impl Parser {
                 Token::BooleanLiteral(_)
                 | Token::StringLiteral(_)
                 | Token::NumberLiteral(_)
+                | Token::FloatLiteral(_)
                 | Token::Identifier(_)
                 | Token::OpNot
-                | Token::OpSub => Node::Unary {
-                    left: Box::new(self.value()),
+                | Token::OpSub => Ok(Node::Unary {
+                    left: Box::new(self.value()?),
                     operation: '-',
-                },
+                    position: start,
+                }),
                 a => self.error(&format!(
                     "SyntaxError: Unexpected token [3] `{:?}`. Expected value",
                     a
                 )),
             },
             Token::ParenLeft => {
-                let expression = self.expression();
+                let expression = self.expression()?;
 
                 if self.get_current() != Token::ParenRight {
-                    self.error("SyntaxError: Expected ')' after expression.");
+                    return self.error("SyntaxError: Expected ')' after expression.");
                 }
 
                 self.advance();
-                expression
+                Ok(expression)
             }
             a => self.error(&format!(
                 "SyntaxError: Unexpected token [4] `{:?}`. Expected value.",
@@ -210,10 +312,17 @@ impl Parser {
         }
     }
 
-    fn from_builder(&mut self, builder: &str) -> Node {
+    fn from_builder(&mut self, builder: &str) -> Result<Node, ()> {
         match builder {
             "unary" => self.value(),
             "additive" => self.additive_expression(),
+            "multiplicative" => self.multiplicative_expression(),
+            "bitwise_and" => self.bitwise_and_expression(),
+            "bitwise_xor" => self.bitwise_xor_expression(),
+            "bitwise_or" => self.bitwise_or_expression(),
+            "comparison" => self.comparison_expression(),
+            "equality" => self.equality_expression(),
+            "logical_and" => self.logical_and_expression(),
             _ => panic!("Unknown builder '{}'", builder),
         }
     }
@@ -222,39 +331,102 @@ impl Parser {
        `builder` -> the function you want to use to parse the left and right sides
        `operators` -> the operators you recognize on this precedence level
     */
-    fn binary_expression(&mut self, builder: &str, operators: Vec<Token>) -> Node {
-        let mut left = self.from_builder(builder);
+    fn binary_expression(&mut self, builder: &str, operators: Vec<Token>) -> Result<Node, ()> {
+        let mut left = self.from_builder(builder)?;
 
         while operators.contains(&self.get_current()) {
             let operator = self.get_current();
+            let position = self.get_current_position();
             self.advance();
 
-            let right = self.from_builder(builder);
+            let right = self.from_builder(builder)?;
 
             left = Node::Binary {
                 left: Box::new(left),
                 right: Box::new(right),
                 operation: operator,
+                position,
+            };
+        }
+
+        Ok(left)
+    }
+
+    // Same shape as `binary_expression`, but builds `Node::Logical` so
+    // `and`/`or` can be short-circuited later instead of always evaluating
+    // both sides.
+    fn logical_expression(&mut self, builder: &str, operators: Vec<Token>) -> Result<Node, ()> {
+        let mut left = self.from_builder(builder)?;
+
+        while operators.contains(&self.get_current()) {
+            let operator = self.get_current();
+            let position = self.get_current_position();
+            self.advance();
+
+            let right = self.from_builder(builder)?;
+
+            left = Node::Logical {
+                left: Box::new(left),
+                right: Box::new(right),
+                operation: operator,
+                position,
             };
         }
 
-        left
+        Ok(left)
+    }
+
+    fn additive_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression("multiplicative", vec![Token::OpAdd, Token::OpSub])
+    }
+
+    fn multiplicative_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression("unary", vec![Token::OpMod, Token::OpMul, Token::OpDiv])
+    }
+
+    fn bitwise_and_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression("additive", vec![Token::BitwiseAnd])
+    }
+
+    fn bitwise_xor_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression("bitwise_and", vec![Token::BitwiseXor])
+    }
+
+    fn bitwise_or_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression("bitwise_xor", vec![Token::BitwiseOr])
+    }
+
+    fn comparison_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression(
+            "bitwise_or",
+            vec![
+                Token::OpLess,
+                Token::OpMore,
+                Token::OpLessEq,
+                Token::OpMoreEq,
+            ],
+        )
+    }
+
+    fn equality_expression(&mut self) -> Result<Node, ()> {
+        self.binary_expression("comparison", vec![Token::OpEq, Token::OpUneq])
     }
 
-    fn additive_expression(&mut self) -> Node {
-        self.binary_expression("unary", vec![Token::OpAdd, Token::OpSub])
+    fn logical_and_expression(&mut self) -> Result<Node, ()> {
+        self.logical_expression("equality", vec![Token::Keyword("and".to_string())])
     }
 
-    fn multiplicative_expression(&mut self) -> Node {
-        self.binary_expression("additive", vec![Token::OpMod, Token::OpMul, Token::OpDiv])
+    fn logical_or_expression(&mut self) -> Result<Node, ()> {
+        self.logical_expression("logical_and", vec![Token::Keyword("or".to_string())])
     }
 
     #[inline(always)]
-    fn expression(&mut self) -> Node {
-        self.multiplicative_expression()
+    fn expression(&mut self) -> Result<Node, ()> {
+        self.logical_or_expression()
     }
 
-    fn variable_init(&mut self) -> Node {
+    fn variable_init(&mut self) -> Result<Node, ()> {
+        let start = self.get_current_position();
         self.advance();
         let mutable = self.tokens[self.current] == Token::Keyword("mut".to_string());
         if mutable {
@@ -262,15 +434,16 @@ impl Parser {
         }
 
         if let Token::Identifier(name) = self.get_current() {
-            self.expect(Token::OpAssign, "Expected assignment operator");
+            self.expect(Token::OpAssign, "Expected assignment operator")?;
             self.advance();
-            let value = self.expression();
+            let value = self.expression()?;
 
-            return Node::VariableAssignment {
+            Ok(Node::VariableAssignment {
                 name,
                 value: Box::new(value),
                 mutable,
-            };
+                position: start,
+            })
         } else if self.get_current() == Token::CurlyLeft {
             let mut properties: Vec<(String, bool)> = vec![];
 
@@ -288,12 +461,12 @@ impl Parser {
                                     self.warn(&format!("Warning: All destructured properties are mutable. `mut` before `{}` is unnecessary.", name))
                                 }
                             }
-                            _ => self.error("Expected identifier after `mut`."),
+                            _ => return self.error("Expected identifier after `mut`."),
                         },
 
-                        _ => self.error("Expected identifier or `mut`."),
+                        _ => return self.error("Expected identifier or `mut`."),
                     },
-                    _ => self.error("Expeced identifier or `mut`."),
+                    _ => return self.error("Expeced identifier or `mut`."),
                 }
 
                 if self.advance() == Token::CurlyRight {
@@ -301,32 +474,147 @@ impl Parser {
                 }
             }
 
-            self.expect(Token::OpAssign, "Expected assignment operator");
+            self.expect(Token::OpAssign, "Expected assignment operator")?;
             self.advance();
 
-            if let Token::Identifier(name) = self.advance() {
-                return Node::VariableDestructureAssignment {
+            let rhs_position = {
+                self.advance();
+                self.get_current_position()
+            };
+            if let Token::Identifier(name) = self.get_current() {
+                Ok(Node::VariableDestructureAssignment {
                     properties,
-                    value: Box::new(Node::Identifier(name)),
+                    value: Box::new(Node::Identifier(name, rhs_position)),
                     mutable,
-                };
+                    position: start,
+                })
             } else {
                 self.error("SyntaxError: Destructured variable right hand side must be a single identifier")
             }
         } else {
-            self.error("SyntaxError: Expected identifier or `{` after `let`");
+            self.error("SyntaxError: Expected identifier or `{` after `let`")
+        }
+    }
+
+    // Parses statements until the matching `}`. A trailing expression with
+    // no terminator is left as the block's last entry, which doubles as its
+    // implicit return value (the Rhai model).
+    fn block(&mut self) -> Result<Vec<Node>, ()> {
+        let mut statements: Vec<Node> = vec![];
+
+        loop {
+            while self.get_current() == Token::OpNewline {
+                self.advance();
+            }
+
+            if self.get_current() == Token::CurlyRight || self.is_at_end() {
+                break;
+            }
+
+            match self.statement() {
+                Ok(node) => statements.push(node),
+                Err(()) => {} // diagnostic already recorded; keep parsing the block
+            }
+        }
+
+        if self.get_current() != Token::CurlyRight {
+            return self.error("SyntaxError: Expected '}' to close block.");
+        }
+
+        self.advance();
+        Ok(statements)
+    }
+
+    fn function_declaration(&mut self, public: bool) -> Result<Node, ()> {
+        let start = self.get_current_position();
+        self.advance(); // past `fn`
+
+        let name = if let Token::Identifier(name) = self.get_current() {
+            name
+        } else {
+            return self.error("SyntaxError: Expected function name after `fn`.");
+        };
+
+        self.expect(Token::ParenLeft, "Expected '(' after function name.")?;
+        self.advance();
+
+        let mut params: Vec<(String, Type)> = vec![];
+
+        loop {
+            if self.get_current() == Token::ParenRight {
+                self.advance();
+                break;
+            } else if self.get_current() == Token::OpComma {
+                self.advance();
+            } else if let Token::Identifier(param_name) = self.get_current() {
+                self.expect(Token::OpColon, "Expected ':' after parameter name.")?;
+                self.advance();
+
+                let param_type = if let Token::Identifier(ref type_name) = self.get_current() {
+                    Type::from_name(type_name)
+                } else {
+                    return self.error("SyntaxError: Expected parameter type.");
+                };
+
+                self.advance();
+                params.push((param_name, param_type));
+            } else {
+                return self.error("SyntaxError: Expected parameter name, ',' or ')'.");
+            }
+        }
+
+        let return_type = if self.get_current() == Token::OpArrow {
+            self.advance();
+
+            if let Token::Identifier(ref type_name) = self.get_current() {
+                let return_type = Type::from_name(type_name);
+                self.advance();
+                Some(return_type)
+            } else {
+                return self.error("SyntaxError: Expected return type after '->'.");
+            }
+        } else {
+            None
+        };
+
+        if self.get_current() != Token::CurlyLeft {
+            return self.error("SyntaxError: Expected '{' to begin function body.");
         }
+        self.advance();
+
+        let body = self.block()?;
+
+        Ok(Node::FunctionDeclaration {
+            name,
+            params,
+            return_type,
+            body,
+            public,
+            position: start,
+        })
     }
 
-    fn statement(&mut self) -> Node {
+    fn statement(&mut self) -> Result<Node, ()> {
         return match self.get_current() {
             Token::Keyword(ref kw) => match kw.as_str() {
                 "let" => self.variable_init(),
+                "fn" => self.function_declaration(false),
+                "pub" => {
+                    self.advance();
+
+                    if self.get_current() != Token::Keyword("fn".to_string()) {
+                        return self.error("SyntaxError: Expected `fn` after `pub`.");
+                    }
+
+                    self.function_declaration(true)
+                }
                 kw => unimplemented!("{:?}", kw),
             },
             Token::NumberLiteral(_)
+            | Token::FloatLiteral(_)
             | Token::StringLiteral(_)
             | Token::BooleanLiteral(_)
+            | Token::Identifier(_)
             | Token::OpNot
             | Token::OpSub
             | Token::ParenLeft
@@ -340,10 +628,37 @@ impl Parser {
     }
 
     pub fn parse(&mut self) {
-        
-        while !self.is_at_end() {
-            let node: Node = self.statement();
-            self.ast.push(node);
+        loop {
+            while self.get_current() == Token::OpNewline {
+                self.advance();
+            }
+
+            if self.is_at_end() {
+                break;
+            }
+
+            match self.statement() {
+                Ok(node) => self.ast.push(node),
+                Err(()) => {} // diagnostic already recorded; synchronize() moved past it
+            }
+        }
+
+        if !self.errors.is_empty() {
+            let lines = self.source.split('\n').collect::<Vec<&str>>();
+
+            for (message, position) in &self.errors {
+                error::print(
+                    message,
+                    &lines,
+                    position.line.saturating_sub(1),
+                    position.col.saturating_sub(1),
+                    error::ErrorType::Fatal,
+                );
+                println!("");
+            }
+
+            println!("{}", "Could not compile due to error(s) above.".red());
+            exit(0);
         }
     }
 }
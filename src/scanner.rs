@@ -1,3 +1,4 @@
+use colored::Colorize;
 use std::process::exit;
 
 use crate::error;
@@ -6,6 +7,7 @@ use crate::error;
 pub enum Token {
     StringLiteral(String),
     NumberLiteral(i64),
+    FloatLiteral(f64),
     BooleanLiteral(bool),
     BuiltinFn(String),
 
@@ -42,6 +44,7 @@ pub enum Token {
     OpPeriod,
     OpNewline,
     OpComma,
+    OpArrow,
 
     End,
 }
@@ -50,29 +53,94 @@ const KEYWORDS: [&str; 10] = [
     "let", "mut", "pub", "fn", "struct", "enum", "from", "import", "or", "and",
 ];
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize, // 1-based
+    pub col: usize,  // 1-based
+}
+
 pub struct Scanner {
     source: String,
+    // Char-indexed view of `source`, built once up front so `get_current`/
+    // `peek`/`advance` are O(1) lookups instead of re-walking the string
+    // from the start on every call.
+    chars: Vec<char>,
     current: usize,
+    line: usize,
+    col: usize,
     pub tokens: Vec<Token>,
+    pub positions: Vec<Position>,
 }
 
 impl Scanner {
     pub fn new(source: String, current: usize) -> Scanner {
+        let chars = source.chars().collect();
+
         Scanner {
             source,
+            chars,
             current,
+            line: 1,
+            col: 1,
             tokens: vec![],
+            positions: vec![],
         }
     }
 
+    // Advances past the char at `current`, updating `line`/`col` to the
+    // position of the char it lands on, then returns that char.
     fn advance(&mut self) -> Option<char> {
+        if let Some(c) = self.get_current() {
+            if c == '\n' {
+                self.line += 1;
+                self.col = 1;
+            } else {
+                self.col += 1;
+            }
+        }
+
         self.current += 1;
-        return self.source.chars().nth(self.current);
+        self.chars.get(self.current).copied()
+    }
+
+    // Undoes a single `advance()` call. Only used right after the
+    // identifier/number loops overshoot by one char, and those loops never
+    // consume a newline, so stepping `col` back by one is always correct.
+    fn retreat(&mut self) {
+        self.current -= 1;
+        self.col -= 1;
     }
 
     #[inline(always)]
     fn get_current(&mut self) -> Option<char> {
-        return self.source.chars().nth(self.current);
+        self.chars.get(self.current).copied()
+    }
+
+    #[inline(always)]
+    fn peek(&mut self) -> Option<char> {
+        self.chars.get(self.current + 1).copied()
+    }
+
+    // If the next char is `second`, consumes it and returns `two`; otherwise
+    // returns `one` and leaves the char unconsumed. Used to turn `=`, `!`,
+    // `<`, `>` into their two-char forms (`==`, `!=`, `<=`, `>=`).
+    fn two_char_op(&mut self, second: char, one: Token, two: Token) -> Token {
+        if self.peek() == Some(second) {
+            self.advance();
+            two
+        } else {
+            one
+        }
+    }
+
+    // Position of the char at `current`, tracked incrementally by
+    // `advance()`/`retreat()` rather than recomputed from scratch.
+    #[inline(always)]
+    fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            col: self.col,
+        }
     }
 
     fn identifier(&mut self) -> Token {
@@ -87,7 +155,7 @@ impl Scanner {
             self.advance();
         }
 
-        self.current -= 1;
+        self.retreat();
         return if KEYWORDS.contains(&string.as_str()) {
             Token::Keyword(string)
         } else {
@@ -107,7 +175,32 @@ impl Scanner {
             self.advance();
         }
 
-        self.current -= 1;
+        // Only treat the `.` as a decimal point if a digit follows, so a
+        // second `.` (`1.2.3`) or method access (`foo.bar`) isn't consumed
+        // as part of the number.
+        if self.get_current() == Some('.') && matches!(self.peek(), Some('0'..='9')) {
+            self.advance(); // consume '.'
+
+            let mut fraction: f64 = 0.0;
+            let mut scale: f64 = 0.1;
+
+            while let Some(c) = self.get_current() {
+                match c {
+                    '0'..='9' => {
+                        fraction += c.to_digit(10).unwrap() as f64 * scale;
+                        scale *= 0.1;
+                    }
+                    _ => break,
+                }
+
+                self.advance();
+            }
+
+            self.retreat();
+            return Token::FloatLiteral(num as f64 + fraction);
+        }
+
+        self.retreat();
         return Token::NumberLiteral(num);
     }
 
@@ -132,38 +225,95 @@ impl Scanner {
         return Token::StringLiteral(string);
     }
 
+    // Line comments run from `#` to end of line; block comments run from
+    // `#{` to `}#` and may span multiple lines. Neither produces a token.
+    fn line_comment(&mut self) {
+        while let Some(c) = self.get_current() {
+            if c == '\n' {
+                break;
+            }
+
+            self.advance();
+        }
+    }
+
+    fn block_comment(&mut self) {
+        let start = self.current_position();
+
+        self.advance(); // '#' -> '{'
+        self.advance(); // '{' -> first content char
+
+        loop {
+            match self.get_current() {
+                Some('}') if self.peek() == Some('#') => {
+                    self.advance(); // '}' -> '#'
+                    self.advance(); // '#' -> past comment
+                    break;
+                }
+                Some(_) => {
+                    self.advance();
+                }
+                None => {
+                    error::print(
+                        "SyntaxError: Unterminated block comment.",
+                        &self.source.split('\n').collect::<Vec<&str>>(),
+                        start.line.saturating_sub(1),
+                        start.col.saturating_sub(1),
+                        error::ErrorType::Fatal,
+                    );
+
+                    println!("{}", "Could not compile due to error above.".red());
+                    exit(0);
+                }
+            }
+        }
+    }
+
     pub fn scan(&mut self) {
         while let Some(c) = self.get_current() {
-            if c.is_whitespace() {  
+            if c.is_whitespace() {
                 if c == '\n' {
                     self.tokens.push(Token::OpNewline);
+                    self.positions.push(self.current_position());
                 }
 
                 self.advance();
                 continue;
             }
 
+            if c == '#' {
+                if self.peek() == Some('{') {
+                    self.block_comment();
+                } else {
+                    self.line_comment();
+                }
+
+                continue;
+            }
+
+            let start = self.current_position();
+
             let token = match c {
                 'a'..='z' | 'A'..='Z' => self.identifier(),
                 '0'..='9' => self.number(),
                 '.' => Token::OpPeriod,
                 '+' => Token::OpAdd,
-                '-' => Token::OpSub,
+                '-' => self.two_char_op('>', Token::OpSub, Token::OpArrow),
                 '*' => Token::OpMul,
                 '/' => Token::OpDiv,
-                '!' => Token::OpNot,
+                '!' => self.two_char_op('=', Token::OpNot, Token::OpUneq),
                 '%' => Token::OpMod,
                 ':' => Token::OpColon,
                 ';' => Token::OpSemicolon,
-                '=' => Token::OpAssign,
+                '=' => self.two_char_op('=', Token::OpAssign, Token::OpEq),
                 '{' => Token::CurlyLeft,
                 '}' => Token::CurlyRight,
                 '(' => Token::ParenLeft,
                 ')' => Token::ParenRight,
                 '[' => Token::SquareLeft,
                 ']' => Token::SquareRight,
-                '>' => Token::OpMore,
-                '<' => Token::OpLess,
+                '>' => self.two_char_op('=', Token::OpMore, Token::OpMoreEq),
+                '<' => self.two_char_op('=', Token::OpLess, Token::OpLessEq),
                 '^' => Token::BitwiseXor,
                 '|' => Token::BitwiseOr,
                 '&' => Token::BitwiseAnd,
@@ -173,6 +323,7 @@ impl Scanner {
             };
 
             self.tokens.push(token.clone());
+            self.positions.push(start);
             self.advance();
         }
     }